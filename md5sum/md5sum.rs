@@ -34,9 +34,11 @@ pub fn uumain(args: Vec<String>) -> int {
 
     let program = args.get(0).clone();
 
+    let (algo, mut digest) = detect_algo(program.as_slice());
+
     let opts = [
         getopts::optflag("b", "binary", "read in binary mode"),
-        getopts::optflag("c", "check", "read MD5 sums from the FILEs and check them"),
+        getopts::optflag("c", "check", "read checksums from the FILEs and check them"),
         getopts::optflag("", "tag", "create a BSD-style checksum"),
         getopts::optflag("t", "text", "read in text mode (default)"),
         getopts::optflag("q", "quiet", "don't print OK for each successfully verified file"),
@@ -58,7 +60,7 @@ pub fn uumain(args: Vec<String>) -> int {
         println!("Usage:");
         println!("  {} [OPTION]... [FILE]...", program);
         println!("");
-        print!("{}", getopts::usage("Compute and check MD5 message digests.", opts));
+        print!("{}", getopts::usage("Compute and check message digests.", opts));
     } else if matches.opt_present("version") {
         println!("{} v{}", NAME, VERSION);
     } else {
@@ -74,7 +76,7 @@ pub fn uumain(args: Vec<String>) -> int {
         } else {
             matches.free
         };
-        match md5sum(files, binary, check, tag, status, quiet, strict, warn) {
+        match md5sum(algo, &mut digest, files, binary, check, tag, status, quiet, strict, warn) {
             Ok(()) => return 0,
             Err(e) => return e
         }
@@ -83,9 +85,25 @@ pub fn uumain(args: Vec<String>) -> int {
     0
 }
 
-fn md5sum(files: Vec<String>, binary: bool, check: bool, tag: bool, status: bool, quiet: bool, strict: bool, warn: bool) -> Result<(), int> {
-    let mut md5 = crypto::md5::Md5::new();
-    let bytes = md5.output_bits() / 4;
+// Select the digest algorithm from the name the binary was invoked as
+// (argv[0]), so one build can be symlinked as md5sum, sha1sum, sha256sum,
+// etc. Unknown names fall back to MD5. The returned string is the upper-case
+// token used in the BSD-style header.
+fn detect_algo(program: &str) -> (&'static str, Box<Digest>) {
+    let name = match Path::new(program).filename_str() {
+        Some(n) => n,
+        None => program
+    };
+    match name {
+        "sha1sum"   => ("SHA1",   box crypto::sha1::Sha1::new()     as Box<Digest>),
+        "sha256sum" => ("SHA256", box crypto::sha2::Sha256::new()   as Box<Digest>),
+        "sha512sum" => ("SHA512", box crypto::sha2::Sha512::new()   as Box<Digest>),
+        _           => ("MD5",    box crypto::md5::Md5::new()       as Box<Digest>)
+    }
+}
+
+fn md5sum(algo: &str, digest: &mut Box<Digest>, files: Vec<String>, binary: bool, check: bool, tag: bool, status: bool, quiet: bool, strict: bool, warn: bool) -> Result<(), int> {
+    let bytes = digest.output_bits() / 4;
     let mut bad_format = 0;
     let mut failed = 0;
     for filename in files.iter() {
@@ -104,7 +122,7 @@ fn md5sum(files: Vec<String>, binary: bool, check: bool, tag: bool, status: bool
                 let line = safe_unwrap!(line);
                 let (ck_filename, sum) = match from_gnu(line.as_slice(), bytes) {
                     Some(m) => m,
-                    None => match from_bsd(line.as_slice(), bytes) {
+                    None => match from_bsd(line.as_slice(), bytes, algo) {
                         Some(m) => m,
                         None => {
                             bad_format += 1;
@@ -112,13 +130,13 @@ fn md5sum(files: Vec<String>, binary: bool, check: bool, tag: bool, status: bool
                                 return Err(1);
                             }
                             if warn {
-                                show_warning!("{}: {}: improperly formatted MD5 checksum line", filename, i + 1);
+                                show_warning!("{}: {}: improperly formatted {} checksum line", filename, i + 1, algo);
                             }
                             continue;
                         }
                     }
                 };
-                let real_sum = calc_sum(&mut md5, &mut safe_unwrap!(File::open(&Path::new(ck_filename))), binary);
+                let real_sum = calc_sum(digest, &mut safe_unwrap!(File::open(&Path::new(ck_filename))), binary);
                 if sum == real_sum.as_slice() {
                     if !quiet {
                         println!("{}: OK", ck_filename);
@@ -131,9 +149,9 @@ fn md5sum(files: Vec<String>, binary: bool, check: bool, tag: bool, status: bool
                 }
             }
         } else {
-            let sum = calc_sum(&mut md5, &mut file, binary);
+            let sum = calc_sum(digest, &mut file, binary);
             if tag {
-                println!("MD5 ({}) = {}", filename, sum);
+                println!("{} ({}) = {}", algo, filename, sum);
             } else {
                 println!("{}  {}", sum, filename);
             }
@@ -153,16 +171,16 @@ fn md5sum(files: Vec<String>, binary: bool, check: bool, tag: bool, status: bool
     Ok(())
 }
 
-fn calc_sum(md5: &mut crypto::md5::Md5, file: &mut Reader, binary: bool) -> String {
+fn calc_sum(digest: &mut Box<Digest>, file: &mut Reader, binary: bool) -> String {
     let data =
         if binary {
             (safe_unwrap!(file.read_to_end()))
         } else {
             (safe_unwrap!(file.read_to_str())).into_bytes()
         };
-    md5.reset();
-    md5.input(data.as_slice());
-    md5.result_str()
+    digest.reset();
+    digest.input(data.as_slice());
+    digest.result_str()
 }
 
 fn from_gnu<'a>(line: &'a str, bytes: uint) -> Option<(&'a str, &'a str)> {
@@ -174,15 +192,24 @@ fn from_gnu<'a>(line: &'a str, bytes: uint) -> Option<(&'a str, &'a str)> {
     }
 }
 
-fn from_bsd<'a>(line: &'a str, bytes: uint) -> Option<(&'a str, &'a str)> {
-    if line.slice(0, 5) == "MD5 (" {
-        let rparen = match line.find(')') {
-            Some(m) => m,
-            None => return None
-        };
-        if rparen > 5 && line.slice(rparen + 1, rparen + 4) == " = " && line.len() - 1 == rparen + 4 + bytes {
-            return Some((line.slice(5, rparen), line.slice(rparen + 4, line.len() - 1)));
-        }
+fn from_bsd<'a>(line: &'a str, bytes: uint, algo: &str) -> Option<(&'a str, &'a str)> {
+    let lparen = match line.find_str(" (") {
+        Some(m) => m,
+        None => return None
+    };
+    // The header token must name the digest we are checking with, so a
+    // `SHA256 (file) = ...` line is not silently accepted by md5sum.
+    if lparen == 0 || line.slice(0, lparen) != algo {
+        return None;
+    }
+    let rparen = match line.find(')') {
+        Some(m) => m,
+        None => return None
+    };
+    // Check the overall length before slicing around `)`, otherwise a line
+    // whose `)` falls within the last few bytes slices out of bounds.
+    if rparen > lparen + 2 && line.len() - 1 == rparen + 4 + bytes && line.slice(rparen + 1, rparen + 4) == " = " {
+        return Some((line.slice(lparen + 2, rparen), line.slice(rparen + 4, line.len() - 1)));
     }
     None
 }
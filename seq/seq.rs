@@ -2,12 +2,10 @@
 
 #![feature(macro_rules)]
 
-// TODO: Make -w flag work with decimals
-// TODO: Support -f flag
-
 extern crate getopts;
 extern crate libc;
 
+use std::cmp::max;
 use std::os;
 
 #[path = "../common/util.rs"]
@@ -15,9 +13,30 @@ mod util;
 
 static NAME: &'static str = "seq";
 
+// A decimal number split into its sign and its integer/fractional digit
+// strings, so sequences can be stepped with exact fixed-point arithmetic
+// instead of accumulating binary-float error.
+struct Decimal {
+    negative: bool,
+    int_digits: String,
+    frac_digits: String,
+}
+
+// A single printf-style conversion directive parsed out of a `-f FORMAT`
+// string, with the literal text that surrounds it. GNU seq only allows one
+// numeric directive per format.
+struct SeqFormat {
+    prefix: String,
+    suffix: String,
+    flags: String,
+    width: uint,
+    precision: uint,
+    conversion: char,
+}
+
 fn print_usage(opts: &[getopts::OptGroup]) {
     println!("seq 1.0.0\n");
-    println!("Usage:\n  seq [-w] [-s string] [-t string] [first [step]] last\n");
+    println!("Usage:\n  seq [-w] [-f format] [-s string] [-t string] [first [step]] last\n");
     println!("{:s}", getopts::usage("Print sequences of numbers", opts));
 }
 
@@ -33,11 +52,319 @@ fn escape_sequences(s: &str) -> String {
         replace("\\t", "\t")
 }
 
+// Parse a plain decimal numeral (optional sign, digits, optional `.` and more
+// digits) into its sign and digit strings. Returns None for anything that is
+// not a plain decimal, e.g. scientific notation, in which case the caller
+// falls back to floating-point stepping.
+fn parse_decimal(s: &str) -> Option<Decimal> {
+    let mut rest = s;
+    let mut negative = false;
+    if rest.starts_with("+") {
+        rest = rest.slice_from(1);
+    } else if rest.starts_with("-") {
+        negative = true;
+        rest = rest.slice_from(1);
+    }
+    if rest.len() == 0 {
+        return None;
+    }
+    let parts: Vec<&str> = rest.split('.').collect();
+    if parts.len() > 2 {
+        return None;
+    }
+    let int_part = *parts.get(0);
+    let frac_part = if parts.len() == 2 { *parts.get(1) } else { "" };
+    if int_part.len() == 0 && frac_part.len() == 0 {
+        return None;
+    }
+    if !int_part.chars().all(|c| c.is_digit()) || !frac_part.chars().all(|c| c.is_digit()) {
+        return None;
+    }
+    Some(Decimal {
+        negative: negative,
+        int_digits: if int_part.len() == 0 { "0".to_string() } else { int_part.to_string() },
+        frac_digits: frac_part.to_string(),
+    })
+}
+
+// Represent a decimal as a signed integer scaled by `10^scale`, padding the
+// fractional part with trailing zeros so every value shares one scale. Returns
+// None when the value does not fit in an i64, so the caller can fall back to
+// float stepping rather than silently emitting a wrong (zeroed) value.
+fn to_scaled(d: &Decimal, scale: uint) -> Option<i64> {
+    let mut frac = d.frac_digits.clone();
+    while frac.len() < scale {
+        frac.push_char('0');
+    }
+    let mut digits = d.int_digits.clone();
+    digits.push_str(frac.as_slice());
+    let mag: Option<i64> = from_str(digits.as_slice());
+    mag.map(|m| if d.negative { -m } else { m })
+}
+
+// Render a scaled integer back to decimal text, reinserting the point `scale`
+// digits from the right and always showing exactly `scale` fractional digits.
+fn render_scaled(value: i64, scale: uint) -> String {
+    let negative = value < 0;
+    let mut digits = value.abs().to_str();
+    if scale == 0 {
+        return if negative { format!("-{:s}", digits) } else { digits };
+    }
+    while digits.len() <= scale {
+        digits = format!("0{:s}", digits);
+    }
+    let point = digits.len() - scale;
+    let body = format!("{:s}.{:s}", digits.as_slice().slice(0, point), digits.as_slice().slice(point, digits.len()));
+    if negative { format!("-{:s}", body) } else { body }
+}
+
+fn scaled_to_f32(value: i64, scale: uint) -> f32 {
+    let mut divisor = 1f64;
+    for _ in range(0, scale) {
+        divisor *= 10f64;
+    }
+    (value as f64 / divisor) as f32
+}
+
+// Width of the integer field of a rendered value, counting a leading sign.
+fn int_width_of(rendered: &str) -> uint {
+    match rendered.as_slice().find('.') {
+        Some(p) => p,
+        None => rendered.len()
+    }
+}
+
+// Left-zero-fill the integer part of a rendered value to `int_width`, inserting
+// the zeros after any sign so decimal points stay aligned in a column. The
+// fractional part is already a fixed `scale` digits wide, so it needs no
+// padding.
+fn pad_integer(rendered: &str, int_width: uint) -> String {
+    let (int_part, frac_part) = match rendered.find('.') {
+        Some(p) => (rendered.slice(0, p), rendered.slice(p, rendered.len())),
+        None => (rendered, "")
+    };
+    let (sign, digits) = if int_part.starts_with("-") {
+        ("-", int_part.slice_from(1))
+    } else {
+        ("", int_part)
+    };
+    let mut out = String::new();
+    out.push_str(sign);
+    let current = sign.len() + digits.len();
+    if current < int_width {
+        for _ in range(0, int_width - current) {
+            out.push_char('0');
+        }
+    }
+    out.push_str(digits);
+    out.push_str(frac_part);
+    out
+}
+
+// Parse a GNU-seq-compatible FORMAT string containing exactly one conversion
+// directive of the form `%[-+ 0#]*[0-9]*(\.[0-9]+)?[eEfgG]`. The literal text
+// before and after the directive (escape sequences already expanded) is kept
+// verbatim and reproduced around every formatted value.
+fn parse_format(fmt: &str) -> Result<SeqFormat, String> {
+    let chars: Vec<char> = fmt.chars().collect();
+    let n = chars.len();
+    let mut prefix = String::new();
+    let mut i = 0;
+    while i < n {
+        if chars[i] == '%' {
+            if i + 1 < n && chars[i + 1] == '%' {
+                prefix.push_char('%');
+                i += 2;
+                continue;
+            }
+            break;
+        }
+        prefix.push_char(chars[i]);
+        i += 1;
+    }
+    if i >= n {
+        return Err(format!("seq: format '{:s}' has no % directive", fmt));
+    }
+    i += 1;
+    let mut flags = String::new();
+    while i < n && "-+ 0#".contains_char(chars[i]) {
+        flags.push_char(chars[i]);
+        i += 1;
+    }
+    let mut width = 0u;
+    while i < n && chars[i].is_digit() {
+        width = width * 10 + (chars[i] as uint - '0' as uint);
+        i += 1;
+    }
+    let mut precision = 6u;
+    if i < n && chars[i] == '.' {
+        i += 1;
+        precision = 0;
+        while i < n && chars[i].is_digit() {
+            precision = precision * 10 + (chars[i] as uint - '0' as uint);
+            i += 1;
+        }
+    }
+    if i >= n {
+        return Err(format!("seq: format '{:s}' ends in the middle of a % directive", fmt));
+    }
+    let conversion = chars[i];
+    if !"eEfgG".contains_char(conversion) {
+        return Err(format!("seq: invalid conversion character '%{:c}' in format", conversion));
+    }
+    i += 1;
+    let mut suffix = String::new();
+    while i < n {
+        if chars[i] == '%' {
+            if i + 1 < n && chars[i + 1] == '%' {
+                suffix.push_char('%');
+                i += 2;
+                continue;
+            }
+            return Err(format!("seq: format '{:s}' has too many % directives", fmt));
+        }
+        suffix.push_char(chars[i]);
+        i += 1;
+    }
+    Ok(SeqFormat {
+        prefix: prefix,
+        suffix: suffix,
+        flags: flags,
+        width: width,
+        precision: precision,
+        conversion: conversion,
+    })
+}
+
+// Compute `10^exp` for a possibly negative integer exponent.
+fn powi10(exp: int) -> f64 {
+    let mut r = 1f64;
+    for _ in range(0, exp.abs() as uint) {
+        r *= 10f64;
+    }
+    if exp < 0 { 1f64 / r } else { r }
+}
+
+// Fixed-point `%f` with trailing zeros (and a dangling point) trimmed, used as
+// the basis for the `%g` shortest form.
+fn strip_trailing_zeros(s: String) -> String {
+    if s.as_slice().contains_char('.') {
+        s.as_slice().trim_right_chars('0').trim_right_chars('.').to_string()
+    } else {
+        s
+    }
+}
+
+// `%e`/`%E` conversion done by hand so the exponent is rendered GNU/printf
+// style as `e±NN` (signed, at least two digits) instead of Rust's `e0`.
+fn format_scientific(mag: f64, precision: uint) -> String {
+    if mag == 0f64 {
+        return format!("{:.*f}e+00", precision, 0f64);
+    }
+    let mut exp = mag.log10().floor() as int;
+    let mut mantissa = mag / powi10(exp);
+    // Rounding to `precision` digits can carry the mantissa up to 10, e.g.
+    // 9.9999 with precision 0; bump the exponent and renormalise if so.
+    let rounded: f64 = from_str(format!("{:.*f}", precision, mantissa).as_slice()).unwrap_or(mantissa);
+    if rounded >= 10f64 {
+        exp += 1;
+        mantissa = mag / powi10(exp);
+    }
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{:s}e{:c}{:02u}", format!("{:.*f}", precision, mantissa), sign, exp.abs() as uint)
+}
+
+// Strip the insignificant trailing zeros from the mantissa of a scientific
+// string while leaving the exponent intact.
+fn strip_scientific_zeros(s: String) -> String {
+    match s.as_slice().find('e') {
+        Some(p) => {
+            let mant = s.as_slice().slice(0, p);
+            let exp = s.as_slice().slice(p, s.len());
+            let mant = if mant.contains_char('.') {
+                mant.trim_right_chars('0').trim_right_chars('.')
+            } else {
+                mant
+            };
+            format!("{:s}{:s}", mant, exp)
+        }
+        None => s
+    }
+}
+
+// `%g`/`%G` conversion: `precision` significant digits, choosing fixed or
+// scientific form by exponent and dropping insignificant zeros unless the `#`
+// flag forces them.
+fn format_shortest(mag: f64, precision: uint, alt: bool) -> String {
+    let prec = if precision == 0 { 1 } else { precision };
+    if mag == 0f64 {
+        let s = format!("{:.*f}", prec - 1, 0f64);
+        return if alt { s } else { strip_trailing_zeros(s) };
+    }
+    let exp = mag.log10().floor() as int;
+    if exp >= -4 && exp < prec as int {
+        let decimals = prec as int - 1 - exp;
+        let decimals = if decimals < 0 { 0 } else { decimals as uint };
+        let s = format!("{:.*f}", decimals, mag);
+        if alt { s } else { strip_trailing_zeros(s) }
+    } else {
+        let s = format_scientific(mag, prec - 1);
+        if alt { s } else { strip_scientific_zeros(s) }
+    }
+}
+
+// Render a single value through the parsed directive, implementing the
+// numeric conversion and the subset of printf flags GNU seq honors.
+fn format_value(fmt: &SeqFormat, value: f32) -> String {
+    let mag = (value as f64).abs();
+    let flags = fmt.flags.as_slice();
+    let body = match fmt.conversion {
+        'f' => format!("{:.*f}", fmt.precision, mag),
+        'e' => format_scientific(mag, fmt.precision),
+        'E' => format_scientific(mag, fmt.precision).replace("e", "E"),
+        'g' => format_shortest(mag, fmt.precision, flags.contains_char('#')),
+        'G' => format_shortest(mag, fmt.precision, flags.contains_char('#')).replace("e", "E"),
+        _   => format!("{}", mag)
+    };
+    let sign = if value < 0f32 {
+        "-"
+    } else if flags.contains_char('+') {
+        "+"
+    } else if flags.contains_char(' ') {
+        " "
+    } else {
+        ""
+    };
+    let content = sign.len() + body.len();
+    let mut out = String::new();
+    if content >= fmt.width {
+        out.push_str(sign);
+        out.push_str(body.as_slice());
+    } else {
+        let padding = fmt.width - content;
+        if flags.contains_char('-') {
+            out.push_str(sign);
+            out.push_str(body.as_slice());
+            for _ in range(0, padding) { out.push_char(' '); }
+        } else if flags.contains_char('0') {
+            out.push_str(sign);
+            for _ in range(0, padding) { out.push_char('0'); }
+            out.push_str(body.as_slice());
+        } else {
+            for _ in range(0, padding) { out.push_char(' '); }
+            out.push_str(sign);
+            out.push_str(body.as_slice());
+        }
+    }
+    format!("{:s}{:s}{:s}", fmt.prefix, out, fmt.suffix)
+}
+
 #[allow(dead_code)]
 fn main() { os::set_exit_status(uumain(os::args())); }
 
 pub fn uumain(args: Vec<String>) -> int {
     let opts = [
+        getopts::optopt("f", "format", "Use printf style floating-point FORMAT", ""),
         getopts::optopt("s", "separator", "Separator character (defaults to \\n)", ""),
         getopts::optopt("t", "terminator", "Terminator character (defaults to separator)", ""),
         getopts::optflag("w", "widths", "Equalize widths of all numbers by padding with zeros"),
@@ -65,28 +392,38 @@ pub fn uumain(args: Vec<String>) -> int {
         return 1;
     }
     let first = if matches.free.len() > 1 {
-        match parse_float(matches.free.get(0).as_slice()) {
-            Ok(n) => n,
-            Err(s) => { show_error!("{:s}", s); return 1; }
-        }
+        matches.free.get(0).clone()
     } else {
-        1.0
+        "1".to_string()
     };
     let step = if matches.free.len() > 2 {
-        match parse_float(matches.free.get(1).as_slice()) {
-            Ok(n) => n,
-            Err(s) => { show_error!("{:s}", s); return 1; }
-        }
+        matches.free.get(1).clone()
     } else {
-        1.0
+        "1".to_string()
     };
-    let last = match parse_float(matches.free.get(matches.free.len()-1).as_slice()) {
-        Ok(n) => n,
-        Err(s) => { show_error!("{:s}", s); return 1; }
+    let last = matches.free.get(matches.free.len()-1).clone();
+    for arg in [first.as_slice(), step.as_slice(), last.as_slice()].iter() {
+        match parse_float(*arg) {
+            Ok(_) => {}
+            Err(s) => { show_error!("{:s}", s); return 1; }
+        }
+    }
+    let format = match matches.opt_str("f") {
+        Some(fmt) => {
+            match parse_format(escape_sequences(fmt.as_slice()).as_slice()) {
+                Ok(f) => Some(f),
+                Err(s) => { show_error!("{:s}", s); return 1; }
+            }
+        }
+        None => None
     };
+    if matches.opt_present("w") && format.is_some() {
+        show_error!("format string may not be specified when printing equal width strings");
+        return 1;
+    }
     let separator = escape_sequences(matches.opt_str("s").unwrap_or("\n".to_string()).as_slice());
     let terminator = escape_sequences(matches.opt_str("t").unwrap_or(separator.to_string()).as_slice());
-    print_seq(first, step, last, separator, terminator, matches.opt_present("w"));
+    print_seq(first.as_slice(), step.as_slice(), last.as_slice(), separator, terminator, matches.opt_present("w"), format);
 
     0
 }
@@ -99,17 +436,89 @@ fn done_printing(next: f32, step: f32, last: f32) -> bool {
     }
 }
 
-fn print_seq(first: f32, step: f32, last: f32, separator: String, terminator: String, pad: bool) {
+fn print_seq(first: &str, step: &str, last: &str, separator: String, terminator: String, pad: bool, format: Option<SeqFormat>) {
+    // Plain decimals that all fit in i64 get exact fixed-point stepping;
+    // anything else (scientific notation, or a range too large to scale into
+    // an i64) falls back to floating-point stepping.
+    match (parse_decimal(first), parse_decimal(step), parse_decimal(last)) {
+        (Some(f), Some(s), Some(l)) => {
+            let scale = max(f.frac_digits.len(), max(s.frac_digits.len(), l.frac_digits.len()));
+            match (to_scaled(&f, scale), to_scaled(&s, scale), to_scaled(&l, scale)) {
+                (Some(first_scaled), Some(step_scaled), Some(last_scaled)) => {
+                    print_seq_decimal(first_scaled, step_scaled, last_scaled, scale, separator, terminator, pad, format);
+                    return;
+                }
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+    print_seq_float(
+        from_str(first).unwrap_or(1f32),
+        from_str(step).unwrap_or(1f32),
+        from_str(last).unwrap_or(1f32),
+        separator, terminator, pad, format);
+}
+
+fn print_seq_decimal(first_scaled: i64, step_scaled: i64, last_scaled: i64, scale: uint, separator: String, terminator: String, pad: bool, format: Option<SeqFormat>) {
+    let ascending = step_scaled >= 0;
+    // The integer-part column is only as wide as the values actually emitted:
+    // the start and the final value reached. The step and the raw `last` are
+    // never printed (a range can stop short of `last`), so they must not widen
+    // the column.
+    let last_value = if step_scaled != 0 {
+        let steps = (last_scaled - first_scaled) / step_scaled;
+        if steps < 0 { first_scaled } else { first_scaled + steps * step_scaled }
+    } else {
+        first_scaled
+    };
+    let int_width = [first_scaled, last_value].iter()
+        .map(|&v| int_width_of(render_scaled(v, scale).as_slice()))
+        .fold(0u, |a, b| max(a, b));
+    let mut value = first_scaled;
+    let mut seen = false;
+    loop {
+        if ascending && value > last_scaled { break; }
+        if !ascending && value < last_scaled { break; }
+        if seen {
+            print!("{:s}", separator);
+        }
+        seen = true;
+        match format {
+            Some(ref fmt) => print!("{:s}", format_value(fmt, scaled_to_f32(value, scale))),
+            None => {
+                let rendered = render_scaled(value, scale);
+                if pad {
+                    print!("{:s}", pad_integer(rendered.as_slice(), int_width));
+                } else {
+                    print!("{:s}", rendered);
+                }
+            }
+        }
+        value += step_scaled;
+        if step_scaled == 0 { break; }
+    }
+    print!("{:s}", terminator);
+}
+
+// Fallback stepping for arguments that are not plain decimals (e.g. scientific
+// notation), preserving the crate's original floating-point behaviour.
+fn print_seq_float(first: f32, step: f32, last: f32, separator: String, terminator: String, pad: bool, format: Option<SeqFormat>) {
     let mut i = first;
     let maxlen = first.max(last).to_str().len();
     while !done_printing(i, step, last) {
-        let ilen = i.to_str().len();
-        if pad && ilen < maxlen {
-            for _ in range(0, maxlen - ilen) {
-                print!("0");
+        match format {
+            Some(ref fmt) => print!("{:s}", format_value(fmt, i)),
+            None => {
+                let ilen = i.to_str().len();
+                if pad && ilen < maxlen {
+                    for _ in range(0, maxlen - ilen) {
+                        print!("0");
+                    }
+                }
+                print!("{:f}", i);
             }
         }
-        print!("{:f}", i);
         i += step;
         if !done_printing(i, step, last) {
             print!("{:s}", separator);
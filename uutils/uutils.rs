@@ -0,0 +1,87 @@
+#![crate_id(name="uutils", vers="1.0.0", author="Arcterus")]
+
+/*
+ * This file is part of the uutils coreutils package.
+ *
+ * (c) Arcterus <arcterus@mail.com>
+ *
+ * For the full copyright and license information, please view the LICENSE
+ * file that was distributed with this source code.
+ */
+
+#![feature(macro_rules)]
+
+extern crate md5sum;
+extern crate seq;
+
+use std::os;
+use std::collections::HashMap;
+
+static NAME: &'static str = "uutils";
+static VERSION: &'static str = "1.0.0";
+
+fn util_map() -> HashMap<&'static str, fn(Vec<String>) -> int> {
+    let mut map: HashMap<&'static str, fn(Vec<String>) -> int> = HashMap::new();
+    // md5sum::uumain re-reads argv[0] to pick the digest, so the SHA aliases
+    // all route to it and select their algorithm from the name they were
+    // dispatched under.
+    map.insert("md5sum", md5sum::uumain);
+    map.insert("sha1sum", md5sum::uumain);
+    map.insert("sha256sum", md5sum::uumain);
+    map.insert("sha512sum", md5sum::uumain);
+    map.insert("seq", seq::uumain);
+    map
+}
+
+fn usage(umap: &HashMap<&'static str, fn(Vec<String>) -> int>) {
+    println!("{} v{}", NAME, VERSION);
+    println!("");
+    println!("Usage:");
+    println!("  {} [util [arguments...]]", NAME);
+    println!("");
+    println!("Currently defined functions:");
+    let mut utils: Vec<&str> = umap.keys().map(|k| *k).collect();
+    utils.sort();
+    for util in utils.iter() {
+        println!("  {}", *util);
+    }
+}
+
+fn main() {
+    let umap = util_map();
+    let args = os::args();
+
+    // Dispatch on argv[0] first, so the binary can be symlinked under each
+    // utility name; strip any directory prefix before the lookup.
+    let binary = Path::new(args.get(0).as_slice());
+    let util = binary.filename_str().unwrap_or("");
+    match umap.find(&util) {
+        Some(&uumain) => {
+            os::set_exit_status(uumain(args));
+            return;
+        }
+        None => {}
+    }
+
+    // Invoked under our own name: the utility is the first free argument.
+    if args.len() >= 2 {
+        let util = args.get(1).as_slice();
+        match umap.find(&util) {
+            Some(&uumain) => {
+                let sub: Vec<String> = args.tail().iter().map(|a| a.clone()).collect();
+                os::set_exit_status(uumain(sub));
+                return;
+            }
+            None => {
+                if util != "--help" && util != "-h" {
+                    println!("{}: applet not found: {}", NAME, util);
+                    usage(&umap);
+                    os::set_exit_status(1);
+                    return;
+                }
+            }
+        }
+    }
+
+    usage(&umap);
+}